@@ -1,9 +1,10 @@
 use proc_macro::TokenStream;
+use proc_macro2::Span;
 use std::collections::HashMap;
 use syn::{
-    parse, AngleBracketedGenericArguments, Attribute, Binding, FnArg, GenericArgument,
-    ImplItem, ImplItemMethod, Item, ItemImpl, Lit, Meta, NestedMeta, PathArguments, ReturnType,
-    Type, TypePath, TypeReference,
+    parse, parse_str, AngleBracketedGenericArguments, Attribute, Binding, Error, Expr, FnArg,
+    GenericArgument, ImplItem, ImplItemMethod, Item, ItemImpl, Lit, LitStr, Meta, NestedMeta,
+    Pat, PathArguments, Result, ReturnType, Type, TypePath, TypeReference,
 };
 
 fn get_attr_map(attr: &Attribute) -> Option<(String, HashMap<String, String>)> {
@@ -37,7 +38,152 @@ fn get_attr_map(attr: &Attribute) -> Option<(String, HashMap<String, String>)> {
     Some((ident, attr_map))
 }
 
-pub fn impl_gql_object(ast: Item) -> TokenStream {
+fn get_arg_defaults(attr: &Attribute) -> Option<HashMap<String, String>> {
+    let meta = attr.interpret_meta();
+
+    let meta_list = match meta {
+        Some(Meta::List(ref meta_list)) if meta_list.ident == "graphql" => meta_list,
+        _ => return None,
+    };
+
+    let mut defaults = HashMap::new();
+
+    for nested in meta_list.nested.iter() {
+        let arg_list = match nested {
+            NestedMeta::Meta(Meta::List(ref arg_list)) if arg_list.ident == "arg" => arg_list,
+            _ => continue,
+        };
+
+        let mut arg_map = HashMap::new();
+        for nested in arg_list.nested.iter() {
+            let value = match nested {
+                NestedMeta::Meta(Meta::NameValue(ref value)) => value,
+                _ => continue,
+            };
+
+            let name = value.ident.to_string();
+            let value = match value.lit {
+                Lit::Str(ref string) => string.value(),
+                _ => continue,
+            };
+
+            arg_map.insert(name, value);
+        }
+
+        if let (Some(name), Some(default)) = (arg_map.get("name"), arg_map.get("default")) {
+            defaults.insert(name.clone(), default.clone());
+        }
+    }
+
+    Some(defaults)
+}
+
+/// Parses `#[graphql(derived(name = "...", into = "..."))]`. The `into` conversion is applied
+/// to the base method's raw return value, so it only works for methods returning a bare value,
+/// not one wrapped in `Result`/`Option` (those would need `Into<T>` on the wrapper itself).
+fn get_derived(attr: &Attribute) -> Option<Vec<(String, Type)>> {
+    let meta = attr.interpret_meta();
+
+    let meta_list = match meta {
+        Some(Meta::List(ref meta_list)) if meta_list.ident == "graphql" => meta_list,
+        _ => return None,
+    };
+
+    let mut derived = Vec::new();
+
+    for nested in meta_list.nested.iter() {
+        let derived_list = match nested {
+            NestedMeta::Meta(Meta::List(ref derived_list)) if derived_list.ident == "derived" => {
+                derived_list
+            }
+            _ => continue,
+        };
+
+        let mut derived_map = HashMap::new();
+        for nested in derived_list.nested.iter() {
+            let value = match nested {
+                NestedMeta::Meta(Meta::NameValue(ref value)) => value,
+                _ => continue,
+            };
+
+            let name = value.ident.to_string();
+            let value = match value.lit {
+                Lit::Str(ref string) => string.value(),
+                _ => continue,
+            };
+
+            derived_map.insert(name, value);
+        }
+
+        if let (Some(name), Some(into)) = (derived_map.get("name"), derived_map.get("into")) {
+            if let Ok(into_ty) = parse_str::<Type>(into) {
+                derived.push((name.clone(), into_ty));
+            }
+        }
+    }
+
+    Some(derived)
+}
+
+fn get_complexity(attr: &Attribute) -> Option<Expr> {
+    let meta = attr.interpret_meta();
+
+    let meta_list = match meta {
+        Some(Meta::List(ref meta_list)) if meta_list.ident == "graphql" => meta_list,
+        _ => return None,
+    };
+
+    meta_list.nested.iter().find_map(|nested| match nested {
+        NestedMeta::Meta(Meta::NameValue(ref value)) if value.ident == "complexity" => {
+            match value.lit {
+                Lit::Str(ref expr) => parse_str::<Expr>(&expr.value()).ok(),
+                Lit::Int(ref n) => parse_str::<Expr>(&n.value().to_string()).ok(),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+fn get_interfaces(attr: &Attribute) -> Option<Vec<Type>> {
+    let meta = attr.interpret_meta();
+
+    let meta_list = match meta {
+        Some(Meta::List(ref meta_list)) if meta_list.ident == "graphql" => meta_list,
+        _ => return None,
+    };
+
+    meta_list.nested.iter().find_map(|nested| match nested {
+        NestedMeta::Meta(Meta::List(ref interfaces)) if interfaces.ident == "interfaces" => {
+            Some(
+                interfaces
+                    .nested
+                    .iter()
+                    .filter_map(|nested| match nested {
+                        NestedMeta::Meta(Meta::Word(ref ident)) => {
+                            parse(quote!(#ident).into()).ok()
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    })
+}
+
+fn references_ident_str(expr: &Expr, name: &str) -> bool {
+    quote!(#expr)
+        .to_string()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|token| token == name)
+}
+
+fn references_ident(expr: &Expr, pat: &Pat) -> bool {
+    references_ident_str(expr, &quote!(#pat).to_string())
+}
+
+pub fn impl_gql_object(ast: Item) -> Result<TokenStream> {
     let ItemImpl {
         attrs,
         defaultness,
@@ -51,41 +197,56 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
     } = if let Item::Impl(imp) = ast {
         imp
     } else {
-        panic!("#[gql_object] Can only be applied to impl blocks");
+        return Err(Error::new_spanned(
+            &ast,
+            "#[gql_object] can only be applied to impl blocks",
+        ));
     };
 
     let (name, context) = if let Type::Path(TypePath { ref mut path, .. }) = *self_ty {
         let context = {
-            let mut segment = path
-                .segments
-                .iter_mut()
-                .last()
-                .expect("Paths can't have 0 segments");
+            let mut segment = match path.segments.iter_mut().last() {
+                Some(segment) => segment,
+                None => {
+                    return Err(Error::new_spanned(&path, "paths can't have 0 segments"));
+                }
+            };
             let context = if let PathArguments::AngleBracketed(AngleBracketedGenericArguments {
                                                                    ref args,
                                                                    ..
                                                                }) = segment.arguments
                 {
-                    args.iter().filter_map(|arg| {
+                    match args.iter().find_map(|arg| {
                         if let GenericArgument::Binding(Binding { ref ident, ref ty, .. }) = arg {
                             if ident == "Context" {
-                                return Some(ty)
+                                return Some(ty.clone())
                             }
                         }
                         None
-                    })
-                        .next()
-                        .map(|ty| ty.clone())
-                        .expect("#[gql_object] requires context to be specified with `impl MyType<Context=MyContext>`")
+                    }) {
+                        Some(context) => context,
+                        None => {
+                            return Err(Error::new_spanned(
+                                &segment,
+                                "#[gql_object] requires context to be specified with `impl MyType<Context=MyContext>`",
+                            ));
+                        }
+                    }
                 } else {
-                panic!("#[gql_object] requires context to be specified with `impl MyType<Context=MyContext>`");
+                return Err(Error::new_spanned(
+                    &segment,
+                    "#[gql_object] requires context to be specified with `impl MyType<Context=MyContext>`",
+                ));
             };
             segment.arguments = PathArguments::None;
             context
         };
         (path.clone(), context)
     } else {
-        panic!("#[gql_object] only works with struct impls");
+        return Err(Error::new_spanned(
+            &self_ty,
+            "#[gql_object] only works with struct impls",
+        ));
     };
 
     let description = attrs
@@ -94,32 +255,57 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
         .find(|(name, _)| name == "graphql")
         .map(|(_, map)| map.get("description").map(|i| i.clone()));
 
+    let interfaces: Vec<Type> = attrs.iter().filter_map(get_interfaces).flatten().collect();
+
     let parsed: TypeReference = parse(quote!(&Executor<#context>).into()).unwrap();
 
     let mut fns = Vec::new();
 
     for item in &mut items {
         match item {
-            ImplItem::Const(..) => panic!("Unexpected const item"),
-            ImplItem::Macro(..) => panic!("Unexpected macro item"),
-            ImplItem::Verbatim(..) => panic!("Unexpected verbatim item"),
-            ImplItem::Type(..) => panic!("Unexpected type item"),
+            ImplItem::Const(ref item) => {
+                return Err(Error::new_spanned(item, "unexpected const item"));
+            }
+            ImplItem::Macro(ref item) => {
+                return Err(Error::new_spanned(item, "unexpected macro item"));
+            }
+            ImplItem::Verbatim(ref item) => {
+                return Err(Error::new_spanned(item, "unexpected verbatim item"));
+            }
+            ImplItem::Type(ref item) => {
+                return Err(Error::new_spanned(item, "unexpected type item"));
+            }
             ImplItem::Method(ImplItemMethod {
                                  sig, ref mut attrs, ..
                              }) => {
-                let (description, deprecated) = if let Some((_, map)) = attrs
+                let (description, deprecated, guard) = if let Some((_, map)) = attrs
                     .iter()
                     .filter_map(get_attr_map)
                     .find(|(name, _)| name == "graphql")
                     {
+                        let guard = match map.get("guard") {
+                            Some(expr) => Some(parse_str::<Expr>(expr).map_err(|_| {
+                                Error::new_spanned(sig, "invalid `guard` expression")
+                            })?),
+                            None => None,
+                        };
                         (
                             map.get("description").map(|i| i.clone()),
                             map.get("deprecated").map(|i| i.clone()),
+                            guard,
                         )
                     } else {
-                    (None, None)
+                    (None, None, None)
                 };
 
+                let arg_defaults: HashMap<String, String> =
+                    attrs.iter().filter_map(get_arg_defaults).flatten().collect();
+
+                let complexity = attrs.iter().find_map(get_complexity);
+
+                let derived: Vec<(String, Type)> =
+                    attrs.iter().filter_map(get_derived).flatten().collect();
+
                 attrs.clear();
 
                 match sig.decl.inputs[0] {
@@ -139,13 +325,51 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
 
                 for arg in sig.decl.inputs.iter().skip(1) {
                     if let FnArg::Captured(arg) = arg {
-                        fn_args.push((arg.pat.clone(), arg.ty.clone()));
+                        let pat = &arg.pat;
+                        let arg_name = quote!(#pat).to_string();
+                        let default = match arg_defaults.get(&arg_name) {
+                            Some(expr) => Some(parse_str::<Expr>(expr).map_err(|_| {
+                                Error::new_spanned(arg, "invalid `default` expression")
+                            })?),
+                            None => None,
+                        };
+                        fn_args.push((arg.pat.clone(), arg.ty.clone(), default));
                     } else {
-                        panic!("invalid arg {:?}", stringify!(arg));
+                        return Err(Error::new_spanned(arg, "invalid argument form"));
                     }
                 }
 
-                fns.push((sig.ident.clone(), fn_args, ret, description, deprecated));
+                let is_async = sig.asyncness.is_some();
+
+                let method_ident = sig.ident.clone();
+                fns.push((
+                    quote!(stringify!(#method_ident)),
+                    sig.ident.clone(),
+                    fn_args.clone(),
+                    ret,
+                    description.clone(),
+                    deprecated.clone(),
+                    is_async,
+                    guard.clone(),
+                    complexity.clone(),
+                    None,
+                ));
+
+                for (derived_name, into_ty) in derived {
+                    let derived_lit = LitStr::new(&derived_name, Span::call_site());
+                    fns.push((
+                        quote!(#derived_lit),
+                        sig.ident.clone(),
+                        fn_args.clone(),
+                        into_ty.clone(),
+                        description.clone(),
+                        deprecated.clone(),
+                        is_async,
+                        guard.clone(),
+                        complexity.clone(),
+                        Some(into_ty),
+                    ));
+                }
             }
         }
     }
@@ -162,35 +386,161 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
         brace_token,
     });
 
-    let exec_fns = fns.iter().map(|(name, args, _, _, _)| {
-        let get_args = args.iter().map(|(arg_name, arg_type)| {
+    let exec_fns = fns
+        .iter()
+        .filter(|(_, _, _, _, _, _, is_async, _, _, _)| !is_async)
+        .map(|(field_name, method_name, args, _, _, _, _, guard, _, into)| {
+            let get_args = args.iter().map(|(arg_name, arg_type, default)| {
+                let value = match default {
+                    Some(default) => quote! {
+                      args.get(&juniper::to_camel_case(stringify!(#arg_name))).unwrap_or_else(|| #default)
+                    },
+                    None => quote! {
+                      args.get(&juniper::to_camel_case(stringify!(#arg_name))).expect("Argument missing - validation must have failed")
+                    },
+                };
+                quote! {
+                  let #arg_name: #arg_type = #value;
+                }
+            });
+
+            let arg_names = args.iter().map(|(name, _, _)| name);
+
+            // `juniper::Guard` is a runtime-crate trait (analogous to `GraphQLType`,
+            // `IntoResolvable`, etc.) that this codegen crate depends on but does not define.
+            let guard_check = match guard {
+                Some(guard) => quote! {
+                  let guard = #guard;
+                  juniper::Guard::check(&guard, executor.context())?;
+                },
+                None => quote!(),
+            };
+
+            let convert = match into {
+                Some(into_ty) => quote!(let result = Into::<#into_ty>::into(result);),
+                None => quote!(),
+            };
+
+            quote! {
+              if field == &juniper::to_camel_case(#field_name) {
+                #(#get_args)*
+                #guard_check
+
+                let result = Self::#method_name(&executor, #( #arg_names ),*);
+                #convert
+                return (juniper::IntoResolvable::into(result, executor.context())).and_then(|res|
+                    match res {
+                      Some((ctx, r)) => executor.replaced_context(ctx).resolve_with_ctx(&(), &r),
+                      None => Ok(juniper::Value::null()),
+                    });
+              }
+            }
+        });
+
+    let exec_fns_async = fns.iter().map(|(field_name, method_name, args, _, _, _, is_async, guard, _, into)| {
+        let get_args = args.iter().map(|(arg_name, arg_type, default)| {
+            let value = match default {
+                Some(default) => quote! {
+                  args.get(&juniper::to_camel_case(stringify!(#arg_name))).unwrap_or_else(|| #default)
+                },
+                None => quote! {
+                  args.get(&juniper::to_camel_case(stringify!(#arg_name))).expect("Argument missing - validation must have failed")
+                },
+            };
             quote! {
-              let #arg_name: #arg_type = args.get(&juniper::to_camel_case(stringify!(#arg_name))).expect("Argument missing - validation must have failed");
+              let #arg_name: #arg_type = #value;
             }
         });
 
-        let arg_names = args.iter().map(|(name, _)| name);
+        let arg_names = args.iter().map(|(name, _, _)| name);
+
+        let guard_check = match guard {
+            Some(guard) => quote! {
+              let guard = #guard;
+              juniper::Guard::check(&guard, executor.context())?;
+            },
+            None => quote!(),
+        };
+
+        let call = if *is_async {
+            quote! { Self::#method_name(&executor, #( #arg_names ),*).await }
+        } else {
+            quote! { Self::#method_name(&executor, #( #arg_names ),*) }
+        };
+
+        let convert = match into {
+            Some(into_ty) => quote!(let result = Into::<#into_ty>::into(result);),
+            None => quote!(),
+        };
 
         quote! {
-          if field == &juniper::to_camel_case(stringify!(#name)) {
-            #(#get_args)*
-
-            let result = Self::#name(&executor, #( #arg_names ),*);
-            return (juniper::IntoResolvable::into(result, executor.context())).and_then(|res|
-                match res {
-                  Some((ctx, r)) => executor.replaced_context(ctx).resolve_with_ctx(&(), &r),
-                  None => Ok(juniper::Value::null()),
-                });
+          if field == &juniper::to_camel_case(#field_name) {
+            return Box::pin(async move {
+              #(#get_args)*
+              #guard_check
+
+              let result = #call;
+              #convert
+              (juniper::IntoResolvable::into(result, executor.context())).and_then(|res|
+                  match res {
+                    Some((ctx, r)) => executor.replaced_context(ctx).resolve_with_ctx(&(), &r),
+                    None => Ok(juniper::Value::null()),
+                  })
+            });
           }
         }
     });
 
     let register_fns = fns
         .iter()
-        .map(|(name, args, ret, description, deprecation)| {
-            let args = args.iter().map(|(arg_name, arg_type)| {
+        .map(|(field_name, _, args, ret, description, deprecation, _, _, complexity, _)| {
+            let complexity_fn = complexity.as_ref().map(|complexity| {
+                let complexity_args: Vec<_> = args
+                    .iter()
+                    .filter(|(arg_name, _, _)| references_ident(complexity, arg_name))
+                    .map(|(arg_name, arg_type, default)| {
+                        let value = match default {
+                            Some(default) => quote! {
+                              args.get(&juniper::to_camel_case(stringify!(#arg_name))).unwrap_or_else(|| #default)
+                            },
+                            None => quote! {
+                              args.get(&juniper::to_camel_case(stringify!(#arg_name))).expect("Argument missing - validation must have failed")
+                            },
+                        };
+                        quote! {
+                          let #arg_name: #arg_type = #value;
+                        }
+                    })
+                    .collect();
+
+                let args_param = if complexity_args.is_empty() {
+                    quote!(_args: &juniper::Arguments)
+                } else {
+                    quote!(args: &juniper::Arguments)
+                };
+
+                let child_complexity_param = if references_ident_str(complexity, "child_complexity") {
+                    quote!(child_complexity: i32)
+                } else {
+                    quote!(_child_complexity: i32)
+                };
+
                 quote! {
-                  .argument(registry.arg::<#arg_type>(&juniper::to_camel_case(stringify!(#arg_name)), info))
+                  .complexity(move |#args_param, #child_complexity_param| {
+                      #(#complexity_args)*
+                      #complexity
+                  })
+                }
+            });
+
+            let args = args.iter().map(|(arg_name, arg_type, default)| {
+                match default {
+                    Some(default) => quote! {
+                      .argument(registry.arg_with_default::<#arg_type>(&juniper::to_camel_case(stringify!(#arg_name)), &#default, info))
+                    },
+                    None => quote! {
+                      .argument(registry.arg::<#arg_type>(&juniper::to_camel_case(stringify!(#arg_name)), info))
+                    },
                 }
             });
 
@@ -207,10 +557,11 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
             quote! {
               fields.push(
                 registry
-                    .field_convert::<#ret, _, Self::Context>(&juniper::to_camel_case(stringify!(#name)), info)
+                    .field_convert::<#ret, _, Self::Context>(&juniper::to_camel_case(#field_name), info)
                     #(#args)*
                     #description
                     #deprecation
+                    #complexity_fn
               );
             }
         });
@@ -220,6 +571,14 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
         None => quote!(),
     };
 
+    let interfaces_init = if interfaces.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            interfaces = Some(vec![#( registry.get_type::<#interfaces>(info) ),*]);
+        }
+    };
+
     let gql_impl = quote! {
       impl juniper::GraphQLType for #name {
         type Context = #context;
@@ -235,6 +594,7 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
           let mut fields = Vec::new();
           let mut interfaces: Option<Vec<Type>> = None;
           #(#register_fns)*
+          #interfaces_init
           let mut mt = registry.build_object_type::<#name>(info, &fields);
 
           #description
@@ -257,7 +617,7 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
         ) -> ExecutionResult {
           #(#exec_fns)*
 
-          panic!("Field {} not found on type {}", field, "Mutation");
+          panic!("Field {} not found on type {}", field, stringify!(#name));
         }
 
         fn concrete_type_name(&self, _: &Self::Context, _: &Self::TypeInfo) -> String {
@@ -266,10 +626,39 @@ pub fn impl_gql_object(ast: Item) -> TokenStream {
       }
     };
 
+    let has_async_fields = fns.iter().any(|(_, _, _, _, _, _, is_async, _, _, _)| *is_async);
+
+    let gql_impl_async = if has_async_fields {
+        quote! {
+          impl juniper::GraphQLTypeAsync for #name
+          where
+            Self: Sync,
+            Self::TypeInfo: Sync,
+            Self::Context: Sync,
+          {
+            #[allow(unused_variables)]
+            fn resolve_field_async<'b>(
+              &'b self,
+              _info: &'b (),
+              field: &'b str,
+              args: &'b juniper::Arguments,
+              executor: &'b juniper::Executor<Self::Context>,
+            ) -> juniper::BoxFuture<'b, ExecutionResult> {
+              #(#exec_fns_async)*
+
+              panic!("Field {} not found on type {}", field, stringify!(#name));
+            }
+          }
+        }
+    } else {
+        quote!()
+    };
+
     let res = quote! {
       #item
       #gql_impl
+      #gql_impl_async
     };
 
-    res.into()
+    Ok(res.into())
 }
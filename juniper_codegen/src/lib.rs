@@ -0,0 +1,14 @@
+mod derive_object_impl;
+
+use proc_macro::TokenStream;
+use syn::Item;
+
+#[proc_macro_attribute]
+pub fn gql_object(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let ast: Item = match syn::parse(input) {
+        Ok(ast) => ast,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    derive_object_impl::impl_gql_object(ast).unwrap_or_else(|e| e.to_compile_error().into())
+}
@@ -0,0 +1,12 @@
+use juniper_codegen::gql_object;
+
+struct Query;
+
+#[gql_object]
+impl Query {
+    fn answer(&self) -> i32 {
+        42
+    }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use juniper_codegen::gql_object;
+
+#[gql_object]
+struct Query;
+
+fn main() {}
@@ -0,0 +1,199 @@
+use juniper::{
+    execute, execute_sync, EmptyMutation, EmptySubscription, ExecutionResult, Executor,
+    FieldResult, RootNode, Type, Variables,
+};
+use juniper_codegen::gql_object;
+
+struct Context;
+impl juniper::Context for Context {}
+
+struct Node;
+
+impl juniper::GraphQLType for Node {
+    type Context = Context;
+    type TypeInfo = ();
+
+    fn name(_: &()) -> Option<&str> {
+        Some("Node")
+    }
+
+    fn meta<'r>(info: &(), registry: &mut juniper::Registry<'r>) -> juniper::meta::MetaType<'r> {
+        let fields = &[registry.field::<juniper::ID>("id", info)];
+        registry.build_interface_type::<Node>(info, fields).into_meta()
+    }
+}
+
+struct Query;
+
+#[gql_object]
+#[graphql(interfaces(Node))]
+impl Query<Context = Context> {
+    fn id(_executor: &Executor<Context>) -> juniper::ID {
+        juniper::ID::new("1")
+    }
+
+    #[graphql(guard = "AdminGuard")]
+    fn secret(_executor: &Executor<Context>) -> i32 {
+        42
+    }
+
+    #[graphql(complexity = "5 + depth", arg(name = "depth", default = "1"))]
+    fn expensive(_executor: &Executor<Context>, depth: i32) -> i32 {
+        depth
+    }
+
+    #[graphql(derived(name = "titleAlias", into = "String"))]
+    fn title(_executor: &Executor<Context>) -> String {
+        "hello".to_string()
+    }
+
+    async fn greeting(_executor: &Executor<Context>, name: String) -> String {
+        format!("hello, {}", name)
+    }
+
+    fn maybe_fails(_executor: &Executor<Context>) -> FieldResult<i32> {
+        Ok(1)
+    }
+}
+
+struct AdminGuard;
+
+impl juniper::Guard<Context> for AdminGuard {
+    fn check(&self, _ctx: &Context) -> FieldResult<()> {
+        Ok(())
+    }
+}
+
+fn schema() -> RootNode<'static, Query, EmptyMutation<Context>, EmptySubscription<Context>> {
+    RootNode::new(
+        Query,
+        EmptyMutation::<Context>::new(),
+        EmptySubscription::<Context>::new(),
+    )
+}
+
+#[test]
+fn resolves_base_field() {
+    let (result, errors) = execute_sync(
+        "{ secret }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    assert_eq!(
+        result.as_object_value().unwrap().get_field_value("secret"),
+        Some(&juniper::Value::scalar(42)),
+    );
+}
+
+#[test]
+fn resolves_derived_field_alongside_base() {
+    let (result, errors) = execute_sync(
+        "{ title titleAlias }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    let obj = result.as_object_value().unwrap();
+    assert_eq!(
+        obj.get_field_value("title"),
+        Some(&juniper::Value::scalar("hello")),
+    );
+    assert_eq!(
+        obj.get_field_value("titleAlias"),
+        Some(&juniper::Value::scalar("hello")),
+    );
+}
+
+#[test]
+fn resolves_field_with_explicit_arg() {
+    let (result, errors) = execute_sync(
+        "{ expensive(depth: 2) }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    assert_eq!(
+        result.as_object_value().unwrap().get_field_value("expensive"),
+        Some(&juniper::Value::scalar(2)),
+    );
+}
+
+#[test]
+fn resolves_field_with_default_arg() {
+    let (result, errors) = execute_sync(
+        "{ expensive }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    assert_eq!(
+        result.as_object_value().unwrap().get_field_value("expensive"),
+        Some(&juniper::Value::scalar(1)),
+    );
+}
+
+#[tokio::test]
+async fn resolves_async_field() {
+    let (result, errors) = execute(
+        "{ greeting(name: \"world\") }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    assert_eq!(
+        result.as_object_value().unwrap().get_field_value("greeting"),
+        Some(&juniper::Value::scalar("hello, world")),
+    );
+}
+
+#[test]
+fn registers_object_as_interface_possible_type() {
+    let (result, errors) = execute_sync(
+        "{ __type(name: \"Node\") { possibleTypes { name } } }",
+        None,
+        &schema(),
+        &Variables::new(),
+        &Context,
+    )
+    .unwrap();
+
+    assert_eq!(errors.len(), 0);
+    let possible_types = result
+        .as_object_value()
+        .unwrap()
+        .get_field_value("__type")
+        .unwrap()
+        .as_object_value()
+        .unwrap()
+        .get_field_value("possibleTypes")
+        .unwrap()
+        .as_list_value()
+        .unwrap();
+
+    assert!(possible_types
+        .iter()
+        .any(|ty| ty.as_object_value().unwrap().get_field_value("name")
+            == Some(&juniper::Value::scalar("Query"))));
+}